@@ -0,0 +1,227 @@
+use crate::occurrence::{DwcValue, Occurrence};
+use crate::calc_epoch_time;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+/// How precisely a resolved occurrence date is actually known, so callers
+/// can widen the Rerun time range instead of pretending every record has
+/// a discrete day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatePrecision {
+    Day,
+    Month,
+    Year,
+    /// Resolved from a `start/end` range; the epoch is the interval midpoint.
+    Range,
+}
+
+/// Verbatim date formats seen often enough in the wild to be worth a try
+/// once structured fields and `eventDate` have failed.
+const VERBATIM_FORMATS: &[&str] = &["%d/%m/%Y", "%Y-%m", "%d %b %Y"];
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Half the width, in seconds, of the window a resolved date actually
+/// covers, so callers can widen the logged Rerun time range instead of
+/// placing every record at a single instant regardless of precision.
+/// `Day` carries no uncertainty and gets a zero-width span.
+pub fn precision_half_span_secs(precision: DatePrecision) -> i64 {
+    match precision {
+        DatePrecision::Day => 0,
+        DatePrecision::Month => 15 * SECS_PER_DAY,
+        DatePrecision::Year => 182 * SECS_PER_DAY,
+        // Only the midpoint survives past `resolve_event_date_str`, not the
+        // original start/end, so approximate with a month-sized window.
+        DatePrecision::Range => 15 * SECS_PER_DAY,
+    }
+}
+
+/// Resolve the best available date for `occurrence`, trying in order:
+/// `eventDate` (single ISO-8601 date/datetime, or a `start/end` range),
+/// `year` + `startDayOfYear`, discrete `year`/`month`/`day`, and finally
+/// `verbatimEventDate` against a small list of common patterns. Returns
+/// `None` if nothing resolves, rather than the historical epoch `-1`.
+pub fn resolve_event_date(occurrence: &Occurrence) -> Option<(i64, DatePrecision)> {
+    if let Some(event_date) = occurrence.get("eventDate").and_then(DwcValue::as_str) {
+        if let Some(resolved) = resolve_event_date_str(event_date) {
+            return Some(resolved);
+        }
+    }
+
+    if let (Some(year), Some(start_doy)) = (
+        occurrence.year(),
+        occurrence.get("startDayOfYear").and_then(DwcValue::as_i64),
+    ) {
+        if let Some(date) = NaiveDate::from_yo_opt(year as i32, start_doy as u32) {
+            return Some((epoch_of_date(date), DatePrecision::Day));
+        }
+    }
+
+    if let (Some(year), Some(month), Some(day)) =
+        (occurrence.year(), occurrence.month(), occurrence.day())
+    {
+        let epoch = calc_epoch_time(year, month, day);
+        if epoch != -1 {
+            return Some((epoch, DatePrecision::Day));
+        }
+    }
+
+    if let Some(verbatim) = occurrence.get("verbatimEventDate").and_then(DwcValue::as_str) {
+        if let Some(resolved) = resolve_verbatim(verbatim) {
+            return Some(resolved);
+        }
+    }
+
+    None
+}
+
+fn resolve_event_date_str(value: &str) -> Option<(i64, DatePrecision)> {
+    if let Some((start, end)) = value.split_once('/') {
+        let start = resolve_single_iso8601(start.trim())?;
+        let end = resolve_single_iso8601(end.trim())?;
+        let midpoint = start.0 + (end.0 - start.0) / 2;
+        return Some((midpoint, DatePrecision::Range));
+    }
+
+    resolve_single_iso8601(value)
+}
+
+/// Parse a single (non-range) ISO-8601 date or datetime, falling back to
+/// partial `%Y-%m` and `%Y` forms, each tagged with the precision it
+/// actually carries.
+fn resolve_single_iso8601(value: &str) -> Option<(i64, DatePrecision)> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some((dt.timestamp(), DatePrecision::Day));
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S") {
+        return Some((dt.and_utc().timestamp(), DatePrecision::Day));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some((epoch_of_date(date), DatePrecision::Day));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&format!("{value}-01"), "%Y-%m-%d") {
+        return Some((epoch_of_date(date), DatePrecision::Month));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&format!("{value}-01-01"), "%Y-%m-%d") {
+        return Some((epoch_of_date(date), DatePrecision::Year));
+    }
+    None
+}
+
+fn resolve_verbatim(value: &str) -> Option<(i64, DatePrecision)> {
+    for format in VERBATIM_FORMATS {
+        let has_day = format.contains("%d");
+        // A NaiveDate can't be built without a day, so pin day-less formats
+        // to the first of the month.
+        let parsed = if has_day {
+            NaiveDate::parse_from_str(value, format)
+        } else {
+            NaiveDate::parse_from_str(&format!("{value}-01"), &format!("{format}-%d"))
+        };
+        if let Ok(date) = parsed {
+            let precision = if has_day {
+                DatePrecision::Day
+            } else {
+                DatePrecision::Month
+            };
+            return Some((epoch_of_date(date), precision));
+        }
+    }
+    None
+}
+
+fn epoch_of_date(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::occurrence::DwcValue;
+
+    fn occurrence_with(fields: &[(&str, DwcValue)]) -> Occurrence {
+        let mut occurrence = Occurrence::default();
+        for (key, value) in fields {
+            occurrence.fields.insert(key.to_string(), value.clone());
+        }
+        occurrence
+    }
+
+    #[test]
+    fn resolves_plain_iso8601_date() {
+        let (epoch, precision) = resolve_single_iso8601("2011-05-01").unwrap();
+        assert_eq!(epoch, epoch_of_date(NaiveDate::from_ymd_opt(2011, 5, 1).unwrap()));
+        assert_eq!(precision, DatePrecision::Day);
+    }
+
+    #[test]
+    fn resolves_partial_year_month() {
+        let (epoch, precision) = resolve_single_iso8601("2011-05").unwrap();
+        assert_eq!(epoch, epoch_of_date(NaiveDate::from_ymd_opt(2011, 5, 1).unwrap()));
+        assert_eq!(precision, DatePrecision::Month);
+    }
+
+    #[test]
+    fn resolves_bare_year() {
+        let (epoch, precision) = resolve_single_iso8601("2011").unwrap();
+        assert_eq!(epoch, epoch_of_date(NaiveDate::from_ymd_opt(2011, 1, 1).unwrap()));
+        assert_eq!(precision, DatePrecision::Year);
+    }
+
+    #[test]
+    fn resolves_range_to_midpoint() {
+        let (epoch, precision) = resolve_event_date_str("2011-05-01/2011-05-07").unwrap();
+        assert_eq!(epoch, epoch_of_date(NaiveDate::from_ymd_opt(2011, 5, 4).unwrap()));
+        assert_eq!(precision, DatePrecision::Range);
+    }
+
+    #[test]
+    fn resolves_verbatim_day_month_year() {
+        let (epoch, precision) = resolve_verbatim("05/01/2011").unwrap();
+        assert_eq!(epoch, epoch_of_date(NaiveDate::from_ymd_opt(2011, 1, 5).unwrap()));
+        assert_eq!(precision, DatePrecision::Day);
+    }
+
+    #[test]
+    fn event_date_takes_priority_over_discrete_fields() {
+        let occurrence = occurrence_with(&[
+            ("eventDate", DwcValue::Str("2011-05-01".to_string())),
+            ("year", DwcValue::Int(1999)),
+            ("month", DwcValue::Int(1)),
+            ("day", DwcValue::Int(1)),
+        ]);
+
+        let (epoch, precision) = resolve_event_date(&occurrence).unwrap();
+        assert_eq!(epoch, epoch_of_date(NaiveDate::from_ymd_opt(2011, 5, 1).unwrap()));
+        assert_eq!(precision, DatePrecision::Day);
+    }
+
+    #[test]
+    fn falls_back_to_year_and_start_day_of_year() {
+        let occurrence = occurrence_with(&[
+            ("year", DwcValue::Int(2011)),
+            ("startDayOfYear", DwcValue::Int(121)),
+        ]);
+
+        let (epoch, precision) = resolve_event_date(&occurrence).unwrap();
+        assert_eq!(epoch, epoch_of_date(NaiveDate::from_yo_opt(2011, 121).unwrap()));
+        assert_eq!(precision, DatePrecision::Day);
+    }
+
+    #[test]
+    fn falls_back_to_verbatim_event_date() {
+        let occurrence = occurrence_with(&[(
+            "verbatimEventDate",
+            DwcValue::Str("05/01/2011".to_string()),
+        )]);
+
+        let (epoch, precision) = resolve_event_date(&occurrence).unwrap();
+        assert_eq!(epoch, epoch_of_date(NaiveDate::from_ymd_opt(2011, 1, 5).unwrap()));
+        assert_eq!(precision, DatePrecision::Day);
+    }
+
+    #[test]
+    fn returns_none_when_nothing_resolves() {
+        let occurrence = Occurrence::default();
+        assert!(resolve_event_date(&occurrence).is_none());
+    }
+}
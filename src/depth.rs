@@ -0,0 +1,123 @@
+use crate::occurrence::Occurrence;
+
+/// Configuration for plotting marine occurrences at their recorded depth
+/// instead of flattening everything onto the same shell above the globe.
+#[derive(Debug, Clone, Copy)]
+pub struct DepthMode {
+    pub enabled: bool,
+    /// Real depths (tens to thousands of meters) are tiny next to Earth's
+    /// radius (6,371 km), so they're multiplied by this factor to stay
+    /// visible when the camera cuts into the sphere.
+    pub vertical_exaggeration: f64,
+    /// Draw a thin line from the surface down to the occurrence for legibility.
+    pub draw_surface_line: bool,
+    /// Depth (in meters) considered "fully deep" for the color gradient;
+    /// deeper records just clamp to the deep end of the gradient.
+    pub max_depth_m: f64,
+}
+
+/// Floor for the exaggerated-depth radius so a deep-sea record (trenches
+/// reach ~11,000 m) can't push the plotted point through the Earth's
+/// center to the opposite hemisphere.
+const MIN_DEPTH_RADIUS_M: f64 = 10_000.0;
+
+impl Default for DepthMode {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            vertical_exaggeration: 1_000.0,
+            draw_surface_line: true,
+            max_depth_m: 2_000.0,
+        }
+    }
+}
+
+/// Radial distance (in meters from Earth's center) at which to plot
+/// `occurrence`, pushed down from `sphere_radius_m` by its recorded depth
+/// (exaggerated by `mode.vertical_exaggeration`), clamped so it never goes
+/// below [`MIN_DEPTH_RADIUS_M`]. Occurrences with no recorded depth stay
+/// on the surface shell.
+pub fn depth_radius_m(occurrence: &Occurrence, sphere_radius_m: f64, mode: &DepthMode) -> f64 {
+    let depth = occurrence.depth().unwrap_or(0.0).max(0.0);
+    (sphere_radius_m - depth * mode.vertical_exaggeration).max(MIN_DEPTH_RADIUS_M)
+}
+
+/// A color along a shallow-to-deep gradient so vertical distribution is
+/// visible, or `None` when `occurrence` has no recorded depth.
+pub fn depth_gradient_color(occurrence: &Occurrence, mode: &DepthMode) -> Option<u32> {
+    let depth = occurrence.depth()?;
+    let t = (depth / mode.max_depth_m).clamp(0.0, 1.0);
+
+    const SHALLOW: (u8, u8, u8) = (0x7F, 0xDF, 0xFF); // light cyan
+    const DEEP: (u8, u8, u8) = (0x04, 0x1E, 0x42); // dark navy
+
+    let lerp = |a: u8, b: u8| -> u8 { (a as f64 + (b as f64 - a as f64) * t).round() as u8 };
+
+    Some(u32::from_be_bytes([
+        lerp(SHALLOW.0, DEEP.0),
+        lerp(SHALLOW.1, DEEP.1),
+        lerp(SHALLOW.2, DEEP.2),
+        0xFF,
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::occurrence::DwcValue;
+
+    fn occurrence_with_depth(depth: f64) -> Occurrence {
+        let mut occurrence = Occurrence::default();
+        occurrence.fields.insert("depth".to_string(), DwcValue::Float(depth));
+        occurrence
+    }
+
+    #[test]
+    fn no_recorded_depth_stays_on_the_surface_shell() {
+        let occurrence = Occurrence::default();
+        let mode = DepthMode::default();
+        assert_eq!(depth_radius_m(&occurrence, 6_371_000.0, &mode), 6_371_000.0);
+    }
+
+    #[test]
+    fn recorded_depth_pushes_the_radius_down_by_the_exaggeration_factor() {
+        let occurrence = occurrence_with_depth(1.0);
+        let mode = DepthMode {
+            vertical_exaggeration: 1_000.0,
+            ..DepthMode::default()
+        };
+        assert_eq!(depth_radius_m(&occurrence, 6_371_000.0, &mode), 6_370_000.0);
+    }
+
+    #[test]
+    fn trench_depth_is_clamped_to_the_minimum_radius_instead_of_going_negative() {
+        // Mariana Trench-scale depth with default exaggeration would push the
+        // radius to a large negative number (through Earth's center) without
+        // the clamp.
+        let occurrence = occurrence_with_depth(11_000.0);
+        let mode = DepthMode::default();
+        assert_eq!(depth_radius_m(&occurrence, 6_371_000.0, &mode), MIN_DEPTH_RADIUS_M);
+    }
+
+    #[test]
+    fn no_recorded_depth_has_no_gradient_color() {
+        let occurrence = Occurrence::default();
+        assert_eq!(depth_gradient_color(&occurrence, &DepthMode::default()), None);
+    }
+
+    #[test]
+    fn shallow_depth_is_near_the_shallow_end_of_the_gradient() {
+        let occurrence = occurrence_with_depth(0.0);
+        let color = depth_gradient_color(&occurrence, &DepthMode::default()).unwrap();
+        assert_eq!(color, 0x7FDFFFFF);
+    }
+
+    #[test]
+    fn depth_at_or_beyond_max_depth_clamps_to_the_deep_end_of_the_gradient() {
+        let mode = DepthMode::default();
+        let at_max = depth_gradient_color(&occurrence_with_depth(mode.max_depth_m), &mode).unwrap();
+        let beyond_max = depth_gradient_color(&occurrence_with_depth(mode.max_depth_m * 10.0), &mode).unwrap();
+        assert_eq!(at_max, 0x041E42FF);
+        assert_eq!(beyond_max, at_max);
+    }
+}
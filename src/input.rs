@@ -0,0 +1,157 @@
+use crate::occurrence::REQUIRED_TERMS;
+use polars::prelude::*;
+use std::fs::File;
+use std::io::Read as _;
+use std::path::Path;
+
+/// Which on-disk shape an occurrence export is in. GBIF's native download
+/// is raw TSV, but large biodiversity corpora (iDigBio/GUODA) ship as
+/// Parquet, and GBIF itself also offers a zipped Darwin Core Archive
+/// (`meta.xml` + `occurrence.txt`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Tsv,
+    Parquet,
+    DarwinCoreArchive,
+}
+
+/// Detect the input format from `file`'s extension, falling back to
+/// sniffing its first few magic bytes when the extension is missing or
+/// unfamiliar (renamed downloads are common for GBIF exports).
+pub fn detect_format(file: &str) -> InputFormat {
+    match Path::new(file).extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("parquet") => return InputFormat::Parquet,
+        Some(ext) if ext.eq_ignore_ascii_case("zip") => return InputFormat::DarwinCoreArchive,
+        Some(ext) if ext.eq_ignore_ascii_case("tsv") || ext.eq_ignore_ascii_case("txt") => {
+            return InputFormat::Tsv
+        }
+        _ => (),
+    }
+
+    sniff_magic_bytes(file).unwrap_or(InputFormat::Tsv)
+}
+
+fn sniff_magic_bytes(file: &str) -> Option<InputFormat> {
+    let mut header = [0u8; 4];
+    File::open(file).ok()?.read_exact(&mut header).ok()?;
+
+    if &header == b"PAR1" {
+        return Some(InputFormat::Parquet);
+    }
+    if header[0..2] == [0x50, 0x4B] {
+        return Some(InputFormat::DarwinCoreArchive);
+    }
+
+    None
+}
+
+/// Load `file` into a DataFrame, auto-detecting TSV, Parquet, or a zipped
+/// Darwin Core Archive. `terms` is pushed down as a column projection
+/// where the reader supports it, so multi-gigabyte Parquet files don't
+/// have to be materialized in full before being cut down to the handful
+/// of columns actually needed.
+pub fn load_dataframe(file: &str, terms: &[&str]) -> DataFrame {
+    match detect_format(file) {
+        InputFormat::Tsv => read_tsv_bytes(std::fs::read(file).expect("Error reading TSV file")),
+        InputFormat::Parquet => read_parquet(file, terms),
+        InputFormat::DarwinCoreArchive => read_dwca(file),
+    }
+}
+
+fn read_tsv_bytes(bytes: Vec<u8>) -> DataFrame {
+    CsvReadOptions::default()
+        .with_has_header(true)
+        .with_parse_options(CsvParseOptions::default().with_separator(b'\t'))
+        .with_infer_schema_length(Some(100_000))
+        .into_reader_with_file_handle(std::io::Cursor::new(bytes))
+        .finish()
+        .unwrap()
+}
+
+fn read_parquet(file: &str, terms: &[&str]) -> DataFrame {
+    let lazy = LazyFrame::scan_parquet(file, ScanArgsParquet::default())
+        .expect("Error opening Parquet file");
+
+    // Not every Parquet export carries every term in DEFAULT_TERMS (e.g. a
+    // narrower iDigBio/GUODA projection), so only select the ones actually
+    // present in this file's schema rather than panicking on the rest.
+    // decimalLatitude/decimalLongitude are always selected regardless of
+    // `terms`, since without them there's nothing to plot.
+    let schema = lazy.collect_schema().expect("Error reading Parquet schema");
+    let projection: Vec<Expr> = REQUIRED_TERMS
+        .iter()
+        .chain(terms.iter())
+        .filter(|term| schema.contains(**term))
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .map(|term| col(*term))
+        .collect();
+
+    lazy.select(projection)
+        .collect()
+        .expect("Error reading Parquet file")
+}
+
+/// GBIF's Darwin Core Archive is a zip of `meta.xml` plus one or more
+/// tab-separated data files; `occurrence.txt` is the one with the
+/// occurrence records we care about.
+fn read_dwca(file: &str) -> DataFrame {
+    let zip_file = File::open(file).expect("Error opening Darwin Core Archive");
+    let mut archive = zip::ZipArchive::new(zip_file).expect("Error reading Darwin Core Archive");
+
+    let mut occurrence_txt = archive
+        .by_name("occurrence.txt")
+        .expect("Darwin Core Archive missing occurrence.txt");
+
+    let mut contents = Vec::new();
+    occurrence_txt
+        .read_to_end(&mut contents)
+        .expect("Error reading occurrence.txt from archive");
+
+    read_tsv_bytes(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_format_from_extension() {
+        assert_eq!(detect_format("records.parquet"), InputFormat::Parquet);
+        assert_eq!(detect_format("records.PARQUET"), InputFormat::Parquet);
+        assert_eq!(detect_format("archive.zip"), InputFormat::DarwinCoreArchive);
+        assert_eq!(detect_format("occurrence.tsv"), InputFormat::Tsv);
+        assert_eq!(detect_format("occurrence.txt"), InputFormat::Tsv);
+    }
+
+    #[test]
+    fn detects_format_from_magic_bytes_when_extension_is_missing() {
+        let dir = std::env::temp_dir();
+
+        let parquet_path = dir.join("dwc_to_rerun_test.parquet_magic");
+        std::fs::write(&parquet_path, b"PAR1rest-of-the-file").unwrap();
+        assert_eq!(detect_format(parquet_path.to_str().unwrap()), InputFormat::Parquet);
+        std::fs::remove_file(&parquet_path).unwrap();
+
+        let zip_path = dir.join("dwc_to_rerun_test.zip_magic");
+        std::fs::write(&zip_path, [0x50u8, 0x4B, 0x03, 0x04]).unwrap();
+        assert_eq!(detect_format(zip_path.to_str().unwrap()), InputFormat::DarwinCoreArchive);
+        std::fs::remove_file(&zip_path).unwrap();
+    }
+
+    #[test]
+    fn falls_back_to_tsv_when_extension_and_magic_bytes_are_unrecognized() {
+        let path = std::env::temp_dir().join("dwc_to_rerun_test.unrecognized_magic");
+        std::fs::write(&path, b"id\tname\n1\tfoo\n").unwrap();
+        assert_eq!(detect_format(path.to_str().unwrap()), InputFormat::Tsv);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reads_tab_separated_bytes_into_a_dataframe() {
+        let df = read_tsv_bytes(b"occurrenceID\tdecimalLatitude\n1\t40.5\n2\t-10.25\n".to_vec());
+        assert_eq!(df.height(), 2);
+        assert_eq!(df.width(), 2);
+        assert!(df.column("decimalLatitude").is_ok());
+    }
+}
@@ -1,16 +1,74 @@
+mod date;
+mod depth;
+mod input;
+mod merge;
+mod occurrence;
+mod quality;
+mod spatial;
+mod taxonomy;
+
 use polars::prelude::*;
 use rerun::*;
 use chrono::NaiveDate;
 use rerun_earth::*;
 
-#[derive(Default, Debug, Clone)]
-pub struct Occurrence {
-    decimal_latitude: f64,
-    decimal_longitude: f64,
-    year: i64,
-    month: i64,
-    day: i64,
-    time: u64, // Epoch time
+pub use date::{precision_half_span_secs, resolve_event_date, DatePrecision};
+pub use depth::{depth_gradient_color, depth_radius_m, DepthMode};
+pub use merge::Merge;
+pub use occurrence::{DwcValue, Occurrence, DEFAULT_TERMS, REQUIRED_TERMS};
+pub use quality::{passes_quality, plot_radius_m, QualityFilter};
+pub use spatial::SpatialIndex;
+pub use taxonomy::{taxon_color, taxon_entity_path};
+
+// Sane bounds for the uncertainty-scaled point radius so a single record
+// with a 0 m or absurdly large reported uncertainty doesn't vanish or
+// swallow the globe.
+const MIN_POINT_RADIUS_M: f64 = 5_000.0;
+const MAX_POINT_RADIUS_M: f64 = 250_000.0;
+const DEFAULT_POINT_RADIUS_M: f64 = 100_000.0;
+
+/// A geographic region/radius filter to apply before plotting, so only the
+/// matching subset of occurrences is logged.
+#[derive(Default, Clone, Copy)]
+struct RegionFilter {
+    /// `(top_lat, bottom_lat, west_lon, east_lon)`
+    bounding_box: Option<(f64, f64, f64, f64)>,
+    /// `(center_lat, center_lon, radius_km)`
+    radius: Option<(f64, f64, f64)>,
+}
+
+/// Resolve `region` and `quality` against `occurrences`, returning the
+/// indices to plot. With no region filter set, every occurrence that
+/// passes the quality gate is returned.
+fn select_indices(
+    occurrences: &[Occurrence],
+    region: RegionFilter,
+    quality: &QualityFilter,
+) -> Vec<usize> {
+    let mut selected: Vec<usize> = if region.bounding_box.is_none() && region.radius.is_none() {
+        (0..occurrences.len()).collect()
+    } else {
+        let index = SpatialIndex::build(occurrences);
+        let mut selected: Vec<usize> = match region.bounding_box {
+            Some((top_lat, bottom_lat, west_lon, east_lon)) => index
+                .query_bounding_box(top_lat, bottom_lat, west_lon, east_lon)
+                .expect("invalid bounding box filter"),
+            None => (0..occurrences.len()).collect(),
+        };
+
+        if let Some((center_lat, center_lon, radius_km)) = region.radius {
+            let within_radius: std::collections::HashSet<usize> = index
+                .query_radius(center_lat, center_lon, radius_km)
+                .into_iter()
+                .collect();
+            selected.retain(|i| within_radius.contains(i));
+        }
+
+        selected
+    };
+
+    selected.retain(|&i| passes_quality(&occurrences[i], quality));
+    selected
 }
 
 pub fn calc_epoch_time(year: i64, month: i64, day: i64) -> i64 {
@@ -21,25 +79,17 @@ pub fn calc_epoch_time(year: i64, month: i64, day: i64) -> i64 {
     }
 }
 
-fn read_gbif_file(file: &str) -> Vec<Occurrence> {
-    // Read the CSV file into a DataFrame
-
-    let df = CsvReadOptions::default()
-        .with_has_header(true)
-        .with_parse_options(CsvParseOptions::default().with_separator(b'\t'))
-        .with_infer_schema_length(Some(100_000))
-        // .with_columns(vec!["decimalLatitude", "decimalLongitude", "year", "month", "day"].into())
-        .try_into_reader_with_file_path(Some(file.into())).unwrap()        
-        .finish().unwrap();
-
-    // Print the DataFrame schema
-    // println!("{:?}", df.schema());
-
-    // name: decimalLatitude, field: Float64
-    // name: decimalLongitude, field: Float64
-    // name: year, field: Int64
-    // name: month, field: Int64
-    // name: day, field: Int64
+/// Read a GBIF/OBIS occurrence export (raw TSV, Parquet, or a zipped
+/// Darwin Core Archive, auto-detected by [`input::detect_format`]), keeping
+/// only `terms` (any Darwin Core column name, typed per its Polars dtype)
+/// plus `decimalLatitude`/`decimalLongitude`, which are always kept
+/// regardless of `terms` (see [`REQUIRED_TERMS`]) since there is nothing to
+/// plot without them. `terms` is *not* widened to cover a resolvable date —
+/// if it omits `eventDate`/`year`/`month`/`day`/`verbatimEventDate` etc.,
+/// [`resolve_event_date`] will find nothing to work with and those records
+/// are dropped for lacking a timeline placement.
+fn read_gbif_file(file: &str, terms: &[&str]) -> Vec<Occurrence> {
+    let df = input::load_dataframe(file, terms);
 
     let df_len = df.height();
     let mut occurrences: Vec<Occurrence> = vec![Occurrence::default(); df_len];
@@ -49,40 +99,13 @@ fn read_gbif_file(file: &str) -> Vec<Occurrence> {
     // Iterate over the rows of the DataFrame
     for col in df.iter() {
         // Polars is column based, so let's lean into that
-        match col.name().to_string().as_str() {
-            "decimalLatitude" => {
-                for (i, value) in col.f64().unwrap().into_iter().enumerate() {
-                    match value {
-                        Some(v) => occurrences[i].decimal_latitude = v,
-                        None => invalid_entries.push(i),
-                    }
-                }
-            }
-            "decimalLongitude" => {
-                for (i, value) in col.f64().unwrap().into_iter().enumerate() {
-                    match value {
-                        Some(v) => occurrences[i].decimal_longitude = v,
-                        None => invalid_entries.push(i),
-                    }
-                }
-            }
-            "year" => {
-                for (i, value) in col.i64().unwrap().into_iter().enumerate() {
-                    occurrences[i].year = value.unwrap_or(1970);
-                }
-            }
-            "month" => {
-                for (i, value) in col.i64().unwrap().into_iter().enumerate() {
-                    occurrences[i].month = value.unwrap_or(1);
-                }
-            }
-            "day" => {
-                for (i, value) in col.i64().unwrap().into_iter().enumerate() {
-                    occurrences[i].day = value.unwrap_or(1);
-                }
-            }
-            _ => (),
+        let name = col.name().as_str();
+        let required = REQUIRED_TERMS.contains(&name);
+        if !required && !terms.contains(&name) {
+            continue;
         }
+
+        occurrence::ingest_column(col, &mut occurrences, required, &mut invalid_entries);
     }
 
     // Remove invalid entries
@@ -94,18 +117,124 @@ fn read_gbif_file(file: &str) -> Vec<Occurrence> {
         occurrences.remove(*i);
     }
 
-    // Convert ytd to epoch time
-    for occurrence in occurrences.iter_mut() {
-        occurrence.time = calc_epoch_time(occurrence.year, occurrence.month, occurrence.day) as u64;
+    // Resolve each record's date, dropping any that can't be placed on the
+    // timeline at all rather than silently logging them at epoch -1.
+    let mut undated = Vec::new();
+    for (i, occurrence) in occurrences.iter_mut().enumerate() {
+        match resolve_event_date(occurrence) {
+            Some((epoch, precision)) => {
+                occurrence.time = epoch;
+                occurrence.date_precision = Some(precision);
+            }
+            None => undated.push(i),
+        }
+    }
+    for i in undated.into_iter().rev() {
+        occurrences.remove(i);
     }
 
     occurrences
 }
 
+/// Log the selected `indices` of `occurrences` to `rec`, one `Points3D` per
+/// record, grouped into entity paths that mirror their Linnaean hierarchy
+/// and colored by a stable hash of their taxon (or, with `depth_mode`
+/// enabled, by the depth gradient for records that have a recorded depth).
+fn log_occurrences(
+    rec: &RecordingStream,
+    occurrences: &[Occurrence],
+    indices: &[usize],
+    timeline: Timeline,
+    sphere_radius: f64,
+    depth_mode: &DepthMode,
+) {
+    for &i in indices {
+        let occurrence = &occurrences[i];
+
+        let point_radius_m = if depth_mode.enabled {
+            depth_radius_m(occurrence, sphere_radius, depth_mode)
+        } else {
+            sphere_radius * 1.02
+        };
+
+        let loc = lat_lon_to_xyz(
+            occurrence.decimal_latitude().unwrap_or_default(),
+            occurrence.decimal_longitude().unwrap_or_default(),
+            point_radius_m,
+        );
+        let loc = (loc[0] as f32, loc[1] as f32, loc[2] as f32);
+
+        // Less-than-day precision (Month/Year/a resolved Range) means the
+        // true event time is only known to fall somewhere in a window
+        // around `occurrence.time`. Rerun's timeline is latest-at, so
+        // logging at a single instant makes the record visible forever
+        // after that instant; bound it to the actual window by logging at
+        // the window's start and explicitly clearing it right after the
+        // window's end.
+        let half_span = occurrence
+            .date_precision
+            .map(precision_half_span_secs)
+            .unwrap_or(0);
+        // Month/Year epochs are the first day of the period, so their
+        // window runs forward from there; a Range epoch is the midpoint.
+        let (window_start, window_end) = match occurrence.date_precision {
+            Some(DatePrecision::Month) | Some(DatePrecision::Year) => {
+                (occurrence.time, occurrence.time + 2 * half_span)
+            }
+            _ => (occurrence.time - half_span, occurrence.time + half_span),
+        };
+
+        let radius =
+            plot_radius_m(occurrence, DEFAULT_POINT_RADIUS_M, MIN_POINT_RADIUS_M, MAX_POINT_RADIUS_M);
+
+        let color = if depth_mode.enabled {
+            depth_gradient_color(occurrence, depth_mode).unwrap_or_else(|| taxon_color(occurrence))
+        } else {
+            taxon_color(occurrence)
+        };
+
+        let entity_path = taxon_entity_path(occurrence, i);
+
+        rec.set_timepoint(
+            TimePoint::default().with(timeline, Time::from_seconds_since_epoch(window_start as f64)),
+        );
+
+        if depth_mode.enabled && depth_mode.draw_surface_line {
+            let surface = lat_lon_to_xyz(
+                occurrence.decimal_latitude().unwrap_or_default(),
+                occurrence.decimal_longitude().unwrap_or_default(),
+                sphere_radius * 1.02,
+            );
+            let surface = (surface[0] as f32, surface[1] as f32, surface[2] as f32);
+            let line = LineStrips3D::new(vec![vec![surface, loc]]).with_colors([color]);
+            rec.log(format!("{entity_path}/depth_line"), &line)
+                .expect("Error logging depth line");
+        }
+
+        let points = Points3D::new(vec![loc])
+            .with_radii([radius as f32])
+            .with_colors([color]);
+
+        rec.log(entity_path.clone(), &points).expect("Error logging points");
+
+        if half_span > 0 {
+            // Bound the record's visibility to the resolved window: right
+            // after it ends, clear the entity (and its `depth_line` child)
+            // so a later timeline scrub doesn't keep resolving latest-at
+            // back to this stale position.
+            rec.set_timepoint(
+                TimePoint::default().with(timeline, Time::from_seconds_since_epoch((window_end + 1) as f64)),
+            );
+            rec.log(entity_path, &Clear::recursive())
+                .expect("Error clearing expired occurrence");
+        }
+    }
+}
+
 fn main() {
     // tiget_shark/occurrence.txt
-    let tiger_shark_occurrences = read_gbif_file("tiger_shark/occurrence.txt");
-    let great_white_shark_occurrences = read_gbif_file("great_white/records-2024-10-23.tsv");   
+    let tiger_shark_occurrences = read_gbif_file("tiger_shark/occurrence.txt", DEFAULT_TERMS);
+    let great_white_shark_occurrences = read_gbif_file("great_white/records-2024-10-23.tsv", DEFAULT_TERMS);
 
     let sphere_radius = 6_371_000.0; // Earth's mean radius in meters
     let max_subdivision_length = 100_000.0; // 100 km
@@ -135,48 +264,36 @@ fn main() {
             subdivision_depth,
         );
 
-    let temporal_timeline = Timeline::new_temporal("Tiger Shark Sightings");
-    println!("Found {} occurrences", tiger_shark_occurrences.len());
-    for (i, occurrence) in tiger_shark_occurrences.iter().enumerate() {
-        // Convert lat and lon
-        let loc = lat_lon_to_xyz(occurrence.decimal_latitude, occurrence.decimal_longitude, sphere_radius * 1.02);
-        // Convert loc to f32 tuple
-        let mut loc = (loc[0] as f32, loc[1] as f32, loc[2] as f32);
-        let time = Time::from_seconds_since_epoch(occurrence.time as f64);
-
-        let timepoint = TimePoint::default();
-        let timepoint = timepoint.with(temporal_timeline, time);
-        rec.set_timepoint(timepoint);
+    // Set to Some(...) to only plot occurrences within a region/radius
+    // instead of the whole file.
+    let region_filter = RegionFilter::default();
+    let quality_filter = QualityFilter::default();
+    // Sharks are marine, so place them by recorded depth rather than a flat shell.
+    let depth_mode = DepthMode {
+        enabled: true,
+        ..DepthMode::default()
+    };
 
-        // Create a Points3D
-        let points = Points3D::new(vec![loc])
-            .with_radii([100_000.0])
-            .with_colors([0xFF0000FF]);
-
-        rec.log(format!("tigershark/{i}"), &points).expect("Error logging points");
-    }        
-
-    println!("Found {} occurrences", tiger_shark_occurrences.len());
-    for (i, occurrence) in great_white_shark_occurrences.iter().enumerate() {
-        // Convert lat and lon
-        let loc = lat_lon_to_xyz(occurrence.decimal_latitude, occurrence.decimal_longitude, sphere_radius * 1.02);
-        // Convert loc to f32 tuple
-        let mut loc = (loc[0] as f32, loc[1] as f32, loc[2] as f32);
-        let time = Time::from_seconds_since_epoch(occurrence.time as f64);
-
-        let timepoint = TimePoint::default();
-        let timepoint = timepoint.with(temporal_timeline, time);
-        rec.set_timepoint(timepoint);
-
-        // Create a Points3D
-        let points = Points3D::new(vec![loc])
-            .with_radii([100_000.0])
-            // Let's color these white
-            .with_colors([0xFFFFFFFF]);
+    let temporal_timeline = Timeline::new_temporal("Occurrence Sightings");
 
-        rec.log(format!("greatwhiteshark/{i}"), &points).expect("Error logging points");
-    }        
+    let (occurrences, merge_report) = tiger_shark_occurrences
+        .merge(great_white_shark_occurrences)
+        .expect("Error merging occurrence sources");
+    println!(
+        "Merged occurrence sources: {} duplicates, {} conflicts resolved, {} unmatched",
+        merge_report.duplicates_resolved, merge_report.conflicts_resolved, merge_report.unmatched
+    );
 
+    let indices = select_indices(&occurrences, region_filter, &quality_filter);
+    println!("Found {} occurrences", indices.len());
+    log_occurrences(
+        &rec,
+        &occurrences,
+        &indices,
+        temporal_timeline,
+        sphere_radius,
+        &depth_mode,
+    );
 }
 
 
@@ -0,0 +1,198 @@
+use crate::occurrence::{DwcValue, Occurrence};
+use std::collections::HashMap;
+
+/// Reserved for merge failures that should abort the whole batch. Currently
+/// none do — a record with no usable ID is simply left unreconciled and
+/// counted in [`MergeReport::unmatched`] instead of failing the merge — but
+/// `merge` keeps returning a `Result` so a future failure mode doesn't
+/// change the signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeError {}
+
+impl std::fmt::Display for MergeError {
+    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {}
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+/// How many duplicate/conflicting records a [`Merge::merge`] call resolved.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Records in `other` that shared an ID with a record already present.
+    pub duplicates_resolved: usize,
+    /// Of those duplicates, how many disagreed on coordinates and were
+    /// resolved by keeping the one with lower `coordinateUncertaintyInMeters`.
+    pub conflicts_resolved: usize,
+    /// Records in `other` with neither `occurrenceID` nor `gbifID`; kept
+    /// as-is (appended, not deduplicated) rather than failing the merge.
+    pub unmatched: usize,
+}
+
+/// Combine multiple occurrence sources into one recording: concatenate,
+/// deduplicate by `occurrenceID` (or `gbifID`), and time-sort, instead of
+/// copy-pasted per-file loops in `main`.
+pub trait Merge: Sized {
+    fn merge(self, other: Self) -> Result<(Self, MergeReport), MergeError>;
+}
+
+impl Merge for Vec<Occurrence> {
+    fn merge(mut self, other: Self) -> Result<(Self, MergeReport), MergeError> {
+        let mut report = MergeReport::default();
+
+        let mut index_by_id: HashMap<String, usize> = HashMap::new();
+        for (i, occurrence) in self.iter().enumerate() {
+            if let Some(id) = identifier(occurrence) {
+                index_by_id.insert(id, i);
+            }
+        }
+
+        for incoming in other {
+            let Some(id) = identifier(&incoming) else {
+                report.unmatched += 1;
+                self.push(incoming);
+                continue;
+            };
+
+            match index_by_id.get(&id) {
+                Some(&existing_index) => {
+                    report.duplicates_resolved += 1;
+                    let existing = &self[existing_index];
+
+                    if coordinates_conflict(existing, &incoming) {
+                        report.conflicts_resolved += 1;
+                        if keep_incoming_coordinates(existing, &incoming) {
+                            self[existing_index] = incoming;
+                        }
+                    }
+                }
+                None => {
+                    index_by_id.insert(id, self.len());
+                    self.push(incoming);
+                }
+            }
+        }
+
+        self.sort_by_key(|occurrence| occurrence.time);
+
+        Ok((self, report))
+    }
+}
+
+/// `occurrenceID` when present (as in GBIF/OBIS exports), else `gbifID`
+/// stringified, since the two source files being merged won't always use
+/// the same identifier column.
+fn identifier(occurrence: &Occurrence) -> Option<String> {
+    if let Some(id) = occurrence.get("occurrenceID").and_then(DwcValue::as_str) {
+        return Some(id.to_string());
+    }
+
+    occurrence
+        .get("gbifID")
+        .and_then(DwcValue::as_i64)
+        .map(|id| id.to_string())
+}
+
+fn coordinates_conflict(a: &Occurrence, b: &Occurrence) -> bool {
+    a.decimal_latitude() != b.decimal_latitude() || a.decimal_longitude() != b.decimal_longitude()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrence_with(fields: &[(&str, DwcValue)]) -> Occurrence {
+        let mut occurrence = Occurrence::default();
+        for (key, value) in fields {
+            occurrence.fields.insert(key.to_string(), value.clone());
+        }
+        occurrence
+    }
+
+    fn occurrence_at(id: &str, lat: f64, lon: f64, uncertainty: Option<f64>) -> Occurrence {
+        let mut fields = vec![
+            ("occurrenceID", DwcValue::Str(id.to_string())),
+            ("decimalLatitude", DwcValue::Float(lat)),
+            ("decimalLongitude", DwcValue::Float(lon)),
+        ];
+        if let Some(uncertainty) = uncertainty {
+            fields.push(("coordinateUncertaintyInMeters", DwcValue::Float(uncertainty)));
+        }
+        occurrence_with(&fields)
+    }
+
+    #[test]
+    fn same_id_identical_coordinates_is_not_a_conflict_and_keeps_existing() {
+        let existing = vec![occurrence_at("a", 1.0, 2.0, None)];
+        let incoming = vec![occurrence_at("a", 1.0, 2.0, None)];
+
+        let (merged, report) = existing.merge(incoming).unwrap();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(report.duplicates_resolved, 1);
+        assert_eq!(report.conflicts_resolved, 0);
+    }
+
+    #[test]
+    fn same_id_conflicting_coordinates_incoming_lower_uncertainty_replaces() {
+        let existing = vec![occurrence_at("a", 1.0, 2.0, Some(100.0))];
+        let incoming = vec![occurrence_at("a", 3.0, 4.0, Some(10.0))];
+
+        let (merged, report) = existing.merge(incoming).unwrap();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(report.conflicts_resolved, 1);
+        assert_eq!(merged[0].decimal_latitude(), Some(3.0));
+        assert_eq!(merged[0].decimal_longitude(), Some(4.0));
+    }
+
+    #[test]
+    fn same_id_conflicting_coordinates_incoming_higher_uncertainty_keeps_existing() {
+        let existing = vec![occurrence_at("a", 1.0, 2.0, Some(10.0))];
+        let incoming = vec![occurrence_at("a", 3.0, 4.0, Some(100.0))];
+
+        let (merged, report) = existing.merge(incoming).unwrap();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(report.conflicts_resolved, 1);
+        assert_eq!(merged[0].decimal_latitude(), Some(1.0));
+        assert_eq!(merged[0].decimal_longitude(), Some(2.0));
+    }
+
+    #[test]
+    fn same_id_conflicting_coordinates_both_uncertainty_none_keeps_existing_without_panicking() {
+        let existing = vec![occurrence_at("a", 1.0, 2.0, None)];
+        let incoming = vec![occurrence_at("a", 3.0, 4.0, None)];
+
+        let (merged, report) = existing.merge(incoming).unwrap();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(report.conflicts_resolved, 1);
+        assert_eq!(merged[0].decimal_latitude(), Some(1.0));
+        assert_eq!(merged[0].decimal_longitude(), Some(2.0));
+    }
+
+    #[test]
+    fn record_with_no_identifier_is_appended_and_counted_as_unmatched() {
+        let existing = vec![occurrence_at("a", 1.0, 2.0, None)];
+        let incoming = vec![Occurrence::default()];
+
+        let (merged, report) = existing.merge(incoming).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(report.unmatched, 1);
+        assert_eq!(report.duplicates_resolved, 0);
+    }
+}
+
+/// Keep the incoming record's coordinates over the existing one's when its
+/// reported `coordinateUncertaintyInMeters` is lower, or when only the
+/// incoming record reports one.
+fn keep_incoming_coordinates(existing: &Occurrence, incoming: &Occurrence) -> bool {
+    match (
+        existing.coordinate_uncertainty_in_meters(),
+        incoming.coordinate_uncertainty_in_meters(),
+    ) {
+        (Some(existing_uncertainty), Some(incoming_uncertainty)) => {
+            incoming_uncertainty < existing_uncertainty
+        }
+        (None, Some(_)) => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,217 @@
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// A typed value for a single Darwin Core term, mirroring the Polars dtypes
+/// we actually see in GBIF/OBIS exports (Float64, Int64, Utf8/String, Boolean).
+#[derive(Debug, Clone, PartialEq)]
+pub enum DwcValue {
+    Float(f64),
+    Int(i64),
+    Str(String),
+    Bool(bool),
+}
+
+impl DwcValue {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DwcValue::Float(v) => Some(*v),
+            DwcValue::Int(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            DwcValue::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            DwcValue::Str(v) => Some(v.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            DwcValue::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+/// A single occurrence record, holding whichever Darwin Core terms were
+/// requested at load time (see [`DEFAULT_TERMS`]) keyed by term name, plus
+/// the resolved epoch `time` used to place it on the Rerun timeline.
+#[derive(Default, Debug, Clone)]
+pub struct Occurrence {
+    pub fields: HashMap<String, DwcValue>,
+    pub time: i64, // Epoch time; negative for pre-1970 dates
+    pub date_precision: Option<crate::date::DatePrecision>,
+}
+
+impl Occurrence {
+    pub fn get(&self, term: &str) -> Option<&DwcValue> {
+        self.fields.get(term)
+    }
+
+    pub fn decimal_latitude(&self) -> Option<f64> {
+        self.get("decimalLatitude").and_then(DwcValue::as_f64)
+    }
+
+    pub fn decimal_longitude(&self) -> Option<f64> {
+        self.get("decimalLongitude").and_then(DwcValue::as_f64)
+    }
+
+    pub fn year(&self) -> Option<i64> {
+        self.get("year").and_then(DwcValue::as_i64)
+    }
+
+    pub fn month(&self) -> Option<i64> {
+        self.get("month").and_then(DwcValue::as_i64)
+    }
+
+    pub fn day(&self) -> Option<i64> {
+        self.get("day").and_then(DwcValue::as_i64)
+    }
+
+    pub fn coordinate_uncertainty_in_meters(&self) -> Option<f64> {
+        self.get("coordinateUncertaintyInMeters").and_then(DwcValue::as_f64)
+    }
+
+    pub fn has_geospatial_issues(&self) -> Option<bool> {
+        self.get("hasGeospatialIssues").and_then(DwcValue::as_bool)
+    }
+
+    pub fn issue(&self) -> Option<&str> {
+        self.get("issue").and_then(DwcValue::as_str)
+    }
+
+    /// Depth in meters below the surface, trying GBIF's `depth` first and
+    /// then the OBIS-style `minimumdepthinmeters`/`maximumdepthinmeters`
+    /// (averaged) and `bathymetry` columns.
+    pub fn depth(&self) -> Option<f64> {
+        if let Some(depth) = self.get("depth").and_then(DwcValue::as_f64) {
+            return Some(depth);
+        }
+
+        let min_depth = self.get("minimumdepthinmeters").and_then(DwcValue::as_f64);
+        let max_depth = self.get("maximumdepthinmeters").and_then(DwcValue::as_f64);
+        match (min_depth, max_depth) {
+            (Some(min), Some(max)) => return Some((min + max) / 2.0),
+            (Some(min), None) => return Some(min),
+            (None, Some(max)) => return Some(max),
+            (None, None) => (),
+        }
+
+        self.get("bathymetry").and_then(DwcValue::as_f64)
+    }
+}
+
+/// Terms without which there's nothing to plot; ingested (and, for readers
+/// that push down a projection, selected) unconditionally regardless of
+/// what subset of `terms` a caller asks for.
+pub const REQUIRED_TERMS: &[&str] = &["decimalLatitude", "decimalLongitude"];
+
+/// Darwin Core terms that `main` asks `read_gbif_file` to keep; that reader
+/// has no default of its own and keeps only the terms it is given plus
+/// [`REQUIRED_TERMS`]. Covers the fields the rest of this crate
+/// currently knows how to plot, color, or filter by.
+pub const DEFAULT_TERMS: &[&str] = &[
+    "decimalLatitude",
+    "decimalLongitude",
+    "year",
+    "month",
+    "day",
+    "eventDate",
+    "verbatimEventDate",
+    "startDayOfYear",
+    "endDayOfYear",
+    "scientificName",
+    "species",
+    "taxonKey",
+    "kingdom",
+    "phylum",
+    "class",
+    "order",
+    "family",
+    "basisOfRecord",
+    "depth",
+    "depthAccuracy",
+    "minimumdepthinmeters",
+    "maximumdepthinmeters",
+    "bathymetry",
+    "coordinateUncertaintyInMeters",
+    "hasGeospatialIssues",
+    "issue",
+    "occurrenceID",
+    "gbifID",
+];
+
+/// Copy one Polars column into the matching field of every [`Occurrence`],
+/// typed per the column's own dtype rather than a hardcoded match on the
+/// term name. Any row where the value is null is recorded in `invalid`
+/// only when `required` is set, so optional terms don't discard rows that
+/// merely lack that one field.
+pub fn ingest_column(
+    col: &Column,
+    occurrences: &mut [Occurrence],
+    required: bool,
+    invalid: &mut Vec<usize>,
+) {
+    let name = col.name().to_string();
+
+    match col.dtype() {
+        DataType::Float64 => {
+            for (i, value) in col.f64().unwrap().into_iter().enumerate() {
+                match value {
+                    Some(v) => {
+                        occurrences[i].fields.insert(name.clone(), DwcValue::Float(v));
+                    }
+                    None if required => invalid.push(i),
+                    None => (),
+                }
+            }
+        }
+        DataType::Int64 => {
+            for (i, value) in col.i64().unwrap().into_iter().enumerate() {
+                match value {
+                    Some(v) => {
+                        occurrences[i].fields.insert(name.clone(), DwcValue::Int(v));
+                    }
+                    None if required => invalid.push(i),
+                    None => (),
+                }
+            }
+        }
+        DataType::Boolean => {
+            for (i, value) in col.bool().unwrap().into_iter().enumerate() {
+                match value {
+                    Some(v) => {
+                        occurrences[i].fields.insert(name.clone(), DwcValue::Bool(v));
+                    }
+                    None if required => invalid.push(i),
+                    None => (),
+                }
+            }
+        }
+        DataType::String => {
+            for (i, value) in col.str().unwrap().into_iter().enumerate() {
+                match value {
+                    Some(v) => {
+                        occurrences[i]
+                            .fields
+                            .insert(name.clone(), DwcValue::Str(v.to_string()));
+                    }
+                    None if required => invalid.push(i),
+                    None => (),
+                }
+            }
+        }
+        // Any other dtype (e.g. Date, Categorical) isn't one we've needed
+        // to plot/filter by yet; skip rather than guess at a conversion.
+        _ => (),
+    }
+}
@@ -0,0 +1,169 @@
+use crate::occurrence::Occurrence;
+use std::collections::HashSet;
+
+/// Coordinate-quality gating so known-bad georeferences (flagged
+/// `hasGeospatialIssues`, a rejected GBIF `issue` flag, or an unreasonably
+/// large `coordinateUncertaintyInMeters`) don't pollute the globe.
+pub struct QualityFilter {
+    /// Drop any record where GBIF itself flagged `hasGeospatialIssues = true`.
+    pub reject_geospatial_issues: bool,
+    /// GBIF `issue` flags (e.g. `ZERO_COORDINATE`, `ON_LAND`) that, if
+    /// present, cause the record to be dropped.
+    pub rejected_issue_flags: HashSet<String>,
+    /// Records with `coordinateUncertaintyInMeters` above this are dropped.
+    /// `None` disables the check.
+    pub max_coordinate_uncertainty_m: Option<f64>,
+}
+
+impl Default for QualityFilter {
+    fn default() -> Self {
+        Self {
+            reject_geospatial_issues: true,
+            rejected_issue_flags: [
+                "ZERO_COORDINATE",
+                "COORDINATE_INVALID",
+                "COORDINATE_OUT_OF_RANGE",
+                "ON_LAND",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            max_coordinate_uncertainty_m: None,
+        }
+    }
+}
+
+/// GBIF's `issue` column is a semicolon-separated list of flag names.
+fn parse_issue_flags(value: &str) -> impl Iterator<Item = &str> {
+    value.split(';').map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Whether `occurrence` survives `filter`'s quality gates.
+pub fn passes_quality(occurrence: &Occurrence, filter: &QualityFilter) -> bool {
+    if filter.reject_geospatial_issues && occurrence.has_geospatial_issues() == Some(true) {
+        return false;
+    }
+
+    if let Some(issue) = occurrence.issue() {
+        if parse_issue_flags(issue).any(|flag| filter.rejected_issue_flags.contains(flag)) {
+            return false;
+        }
+    }
+
+    if let Some(max_uncertainty) = filter.max_coordinate_uncertainty_m {
+        if let Some(uncertainty) = occurrence.coordinate_uncertainty_in_meters() {
+            if uncertainty > max_uncertainty {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Radius (in meters) to plot `occurrence` at, honestly conveying
+/// georeference precision: scaled to `coordinateUncertaintyInMeters` when
+/// known, clamped to `[min_radius_m, max_radius_m]`, and falling back to
+/// `default_radius_m` when uncertainty wasn't reported.
+pub fn plot_radius_m(
+    occurrence: &Occurrence,
+    default_radius_m: f64,
+    min_radius_m: f64,
+    max_radius_m: f64,
+) -> f64 {
+    match occurrence.coordinate_uncertainty_in_meters() {
+        Some(uncertainty) => uncertainty.clamp(min_radius_m, max_radius_m),
+        None => default_radius_m,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::occurrence::DwcValue;
+
+    fn occurrence_with(fields: &[(&str, DwcValue)]) -> Occurrence {
+        let mut occurrence = Occurrence::default();
+        for (key, value) in fields {
+            occurrence.fields.insert(key.to_string(), value.clone());
+        }
+        occurrence
+    }
+
+    #[test]
+    fn rejects_flagged_geospatial_issues() {
+        let occurrence = occurrence_with(&[("hasGeospatialIssues", DwcValue::Bool(true))]);
+        assert!(!passes_quality(&occurrence, &QualityFilter::default()));
+    }
+
+    #[test]
+    fn passes_when_geospatial_issues_not_flagged() {
+        let occurrence = occurrence_with(&[("hasGeospatialIssues", DwcValue::Bool(false))]);
+        assert!(passes_quality(&occurrence, &QualityFilter::default()));
+    }
+
+    #[test]
+    fn rejects_each_default_rejected_issue_flag() {
+        for flag in ["ZERO_COORDINATE", "COORDINATE_INVALID", "COORDINATE_OUT_OF_RANGE", "ON_LAND"] {
+            let occurrence = occurrence_with(&[("issue", DwcValue::Str(flag.to_string()))]);
+            assert!(
+                !passes_quality(&occurrence, &QualityFilter::default()),
+                "expected {flag} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_one_rejected_flag_among_a_semicolon_separated_list() {
+        let occurrence = occurrence_with(&[(
+            "issue",
+            DwcValue::Str("SOME_OTHER_FLAG; ON_LAND ;YET_ANOTHER".to_string()),
+        )]);
+        assert!(!passes_quality(&occurrence, &QualityFilter::default()));
+    }
+
+    #[test]
+    fn passes_issue_flags_not_in_the_rejected_set() {
+        let occurrence = occurrence_with(&[(
+            "issue",
+            DwcValue::Str("SOME_OTHER_FLAG;YET_ANOTHER".to_string()),
+        )]);
+        assert!(passes_quality(&occurrence, &QualityFilter::default()));
+    }
+
+    #[test]
+    fn max_uncertainty_passes_below_threshold() {
+        let occurrence = occurrence_with(&[("coordinateUncertaintyInMeters", DwcValue::Float(50.0))]);
+        let filter = QualityFilter {
+            max_coordinate_uncertainty_m: Some(100.0),
+            ..QualityFilter::default()
+        };
+        assert!(passes_quality(&occurrence, &filter));
+    }
+
+    #[test]
+    fn max_uncertainty_passes_at_the_boundary() {
+        let occurrence = occurrence_with(&[("coordinateUncertaintyInMeters", DwcValue::Float(100.0))]);
+        let filter = QualityFilter {
+            max_coordinate_uncertainty_m: Some(100.0),
+            ..QualityFilter::default()
+        };
+        assert!(passes_quality(&occurrence, &filter));
+    }
+
+    #[test]
+    fn max_uncertainty_rejects_above_threshold() {
+        let occurrence = occurrence_with(&[("coordinateUncertaintyInMeters", DwcValue::Float(100.1))]);
+        let filter = QualityFilter {
+            max_coordinate_uncertainty_m: Some(100.0),
+            ..QualityFilter::default()
+        };
+        assert!(!passes_quality(&occurrence, &filter));
+    }
+
+    #[test]
+    fn max_uncertainty_disabled_by_default() {
+        let occurrence = occurrence_with(&[("coordinateUncertaintyInMeters", DwcValue::Float(1_000_000.0))]);
+        assert!(passes_quality(&occurrence, &QualityFilter::default()));
+    }
+}
@@ -0,0 +1,197 @@
+use crate::occurrence::Occurrence;
+use rstar::{RTree, RTreeObject, AABB};
+
+/// Earth's mean radius in kilometers, used for haversine great-circle
+/// distance. Matches the `sphere_radius` used elsewhere for plotting
+/// (6,371,000 m).
+pub const EARTH_RADIUS_KM: f64 = 6_371.0;
+
+/// A query asked for a bounding box whose top latitude is below its
+/// bottom latitude, which has no valid interpretation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidBoundingBox {
+    pub top_lat: f64,
+    pub bottom_lat: f64,
+}
+
+impl std::fmt::Display for InvalidBoundingBox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "bounding box top latitude ({}) must be >= bottom latitude ({})",
+            self.top_lat, self.bottom_lat
+        )
+    }
+}
+
+impl std::error::Error for InvalidBoundingBox {}
+
+/// Great-circle distance in kilometers between two lat/lon points, via the
+/// haversine formula against the Earth's mean radius.
+pub fn distance_between_two_points(a_lat: f64, a_lon: f64, b_lat: f64, b_lon: f64) -> f64 {
+    let (a_lat_r, a_lon_r) = (a_lat.to_radians(), a_lon.to_radians());
+    let (b_lat_r, b_lon_r) = (b_lat.to_radians(), b_lon.to_radians());
+    let d_lat = b_lat_r - a_lat_r;
+    let d_lon = b_lon_r - a_lon_r;
+
+    let h = (d_lat / 2.0).sin().powi(2)
+        + a_lat_r.cos() * b_lat_r.cos() * (d_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+/// One indexed occurrence, stored as `[lon, lat]` since that's the `[x, y]`
+/// order `rstar`'s `AABB`/envelope machinery expects.
+#[derive(Debug, Clone, Copy)]
+struct IndexedPoint {
+    index: usize,
+    lon: f64,
+    lat: f64,
+}
+
+impl RTreeObject for IndexedPoint {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point([self.lon, self.lat])
+    }
+}
+
+/// An `rstar`-backed spatial index over the lat/lon of a set of
+/// [`Occurrence`]s, exposing bounding-box and radius queries so `main` can
+/// log only the matching subset instead of every point in the file.
+pub struct SpatialIndex {
+    tree: RTree<IndexedPoint>,
+}
+
+impl SpatialIndex {
+    /// Index every occurrence that has both `decimalLatitude` and
+    /// `decimalLongitude`; occurrences missing either are simply absent
+    /// from query results.
+    pub fn build(occurrences: &[Occurrence]) -> Self {
+        let points = occurrences
+            .iter()
+            .enumerate()
+            .filter_map(|(index, occurrence)| {
+                let lat = occurrence.decimal_latitude()?;
+                let lon = occurrence.decimal_longitude()?;
+                Some(IndexedPoint { index, lon, lat })
+            })
+            .collect();
+
+        Self {
+            tree: RTree::bulk_load(points),
+        }
+    }
+
+    /// Indices of occurrences within `[bottom_lat, top_lat] x [west_lon, east_lon]`.
+    pub fn query_bounding_box(
+        &self,
+        top_lat: f64,
+        bottom_lat: f64,
+        west_lon: f64,
+        east_lon: f64,
+    ) -> Result<Vec<usize>, InvalidBoundingBox> {
+        if top_lat < bottom_lat {
+            return Err(InvalidBoundingBox { top_lat, bottom_lat });
+        }
+
+        let envelope = AABB::from_corners([west_lon, bottom_lat], [east_lon, top_lat]);
+        Ok(self
+            .tree
+            .locate_in_envelope(&envelope)
+            .map(|point| point.index)
+            .collect())
+    }
+
+    /// Indices of occurrences within `radius_km` great-circle distance of
+    /// `(center_lat, center_lon)`. Uses the tree's envelope as a cheap
+    /// broad-phase filter (with a generous margin), then refines with the
+    /// exact haversine distance since lat/lon degrees aren't equal-area.
+    pub fn query_radius(&self, center_lat: f64, center_lon: f64, radius_km: f64) -> Vec<usize> {
+        const KM_PER_DEGREE_LAT: f64 = 111.0;
+        const MIN_COS_LAT: f64 = 0.01; // keep the longitude margin from blowing up near the poles
+
+        let lat_margin_deg = (radius_km / KM_PER_DEGREE_LAT) * 1.5;
+        // A degree of longitude shrinks by cos(latitude) away from the
+        // equator, so widen the longitude margin to compensate or the
+        // broad-phase envelope excludes points near the poles.
+        let lon_margin_deg = lat_margin_deg / center_lat.to_radians().cos().abs().max(MIN_COS_LAT);
+
+        let envelope = AABB::from_corners(
+            [center_lon - lon_margin_deg, center_lat - lat_margin_deg],
+            [center_lon + lon_margin_deg, center_lat + lat_margin_deg],
+        );
+
+        self.tree
+            .locate_in_envelope(&envelope)
+            .filter(|point| {
+                distance_between_two_points(center_lat, center_lon, point.lat, point.lon)
+                    <= radius_km
+            })
+            .map(|point| point.index)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::occurrence::DwcValue;
+
+    fn occurrence_at(lat: f64, lon: f64) -> Occurrence {
+        let mut occurrence = Occurrence::default();
+        occurrence.fields.insert("decimalLatitude".to_string(), DwcValue::Float(lat));
+        occurrence.fields.insert("decimalLongitude".to_string(), DwcValue::Float(lon));
+        occurrence
+    }
+
+    #[test]
+    fn distance_between_identical_points_is_zero() {
+        assert_eq!(distance_between_two_points(40.0, -74.0, 40.0, -74.0), 0.0);
+    }
+
+    #[test]
+    fn distance_matches_known_great_circle_distance() {
+        // New York (40.7128, -74.0060) to London (51.5074, -0.1278): ~5570 km.
+        let km = distance_between_two_points(40.7128, -74.0060, 51.5074, -0.1278);
+        assert!((km - 5570.0).abs() < 20.0, "expected ~5570 km, got {km}");
+    }
+
+    #[test]
+    fn bounding_box_rejects_top_below_bottom() {
+        let index = SpatialIndex::build(&[]);
+        let err = index.query_bounding_box(10.0, 20.0, -5.0, 5.0).unwrap_err();
+        assert_eq!(err, InvalidBoundingBox { top_lat: 10.0, bottom_lat: 20.0 });
+    }
+
+    #[test]
+    fn bounding_box_finds_points_inside_and_excludes_points_outside() {
+        let occurrences = vec![occurrence_at(10.0, 10.0), occurrence_at(50.0, 50.0)];
+        let index = SpatialIndex::build(&occurrences);
+
+        let found = index.query_bounding_box(20.0, 0.0, 0.0, 20.0).unwrap();
+        assert_eq!(found, vec![0]);
+    }
+
+    #[test]
+    fn radius_query_finds_nearby_point_and_excludes_far_point() {
+        let occurrences = vec![occurrence_at(40.70, -74.00), occurrence_at(51.51, -0.13)];
+        let index = SpatialIndex::build(&occurrences);
+
+        let found = index.query_radius(40.7128, -74.0060, 50.0);
+        assert_eq!(found, vec![0]);
+    }
+
+    #[test]
+    fn radius_query_accounts_for_longitude_shrinking_near_poles() {
+        // At high latitude, a degree of longitude covers far less ground,
+        // so a point offset mostly in longitude should still be found
+        // within a modest radius.
+        let occurrences = vec![occurrence_at(80.0, 10.0)];
+        let index = SpatialIndex::build(&occurrences);
+
+        let found = index.query_radius(80.0, 0.0, 220.0);
+        assert_eq!(found, vec![0]);
+    }
+}
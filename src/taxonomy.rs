@@ -0,0 +1,166 @@
+use crate::occurrence::{DwcValue, Occurrence};
+
+/// Linnaean ranks we have columns for, high to low, used to build an
+/// entity path that mirrors the classification instead of a flat
+/// per-species list.
+const RANKS: &[&str] = &["kingdom", "phylum", "class", "order", "family"];
+
+/// Build a Rerun entity path for `occurrence` that mirrors its Linnaean
+/// hierarchy, e.g. `Animalia/Chordata/Chondrichthyes/Carcharodon_carcharias/{index}`.
+/// Missing ranks are simply omitted rather than plotted as "Unknown/Unknown/…".
+pub fn taxon_entity_path(occurrence: &Occurrence, index: usize) -> String {
+    let mut segments: Vec<String> = RANKS
+        .iter()
+        .filter_map(|rank| occurrence.get(rank).and_then(DwcValue::as_str))
+        .map(sanitize_segment)
+        .collect();
+
+    let species = occurrence
+        .get("scientificName")
+        .and_then(DwcValue::as_str)
+        .or_else(|| occurrence.get("species").and_then(DwcValue::as_str))
+        .map(sanitize_segment)
+        .unwrap_or_else(|| "Unclassified".to_string());
+
+    segments.push(species);
+    segments.push(index.to_string());
+    segments.join("/")
+}
+
+fn sanitize_segment(raw: &str) -> String {
+    raw.trim().replace(' ', "_").replace('/', "_")
+}
+
+/// A stable, visually distinct `0xRRGGBBAA` color for a taxon, derived by
+/// hashing its `taxonKey` (falling back to its `scientificName` when no key
+/// is present) so the same taxon always gets the same color across runs.
+pub fn taxon_color(occurrence: &Occurrence) -> u32 {
+    let seed = occurrence
+        .get("taxonKey")
+        .and_then(DwcValue::as_i64)
+        .map(|key| key as u64)
+        .unwrap_or_else(|| {
+            hash_str(
+                occurrence
+                    .get("scientificName")
+                    .and_then(DwcValue::as_str)
+                    .unwrap_or("unknown"),
+            )
+        });
+
+    let hash = splitmix64(seed);
+    let hue = (hash % 360) as f32;
+    let (r, g, b) = hsv_to_rgb(hue, 0.65, 0.85);
+    u32::from_be_bytes([r, g, b, 0xFF])
+}
+
+fn hash_str(s: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    s.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// SplitMix64, used purely to spread taxon keys/name hashes evenly across
+/// the hue wheel; cryptographic strength isn't needed here.
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
+    let c = v * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = v - c;
+    let (r1, g1, b1) = match h as u32 {
+        0..=59 => (c, x, 0.0),
+        60..=119 => (x, c, 0.0),
+        120..=179 => (0.0, c, x),
+        180..=239 => (0.0, x, c),
+        240..=299 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    (
+        ((r1 + m) * 255.0) as u8,
+        ((g1 + m) * 255.0) as u8,
+        ((b1 + m) * 255.0) as u8,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrence_with(fields: &[(&str, DwcValue)]) -> Occurrence {
+        let mut occurrence = Occurrence::default();
+        for (key, value) in fields {
+            occurrence.fields.insert(key.to_string(), value.clone());
+        }
+        occurrence
+    }
+
+    #[test]
+    fn entity_path_includes_full_hierarchy_and_index() {
+        let occurrence = occurrence_with(&[
+            ("kingdom", DwcValue::Str("Animalia".to_string())),
+            ("phylum", DwcValue::Str("Chordata".to_string())),
+            ("class", DwcValue::Str("Chondrichthyes".to_string())),
+            ("order", DwcValue::Str("Lamniformes".to_string())),
+            ("family", DwcValue::Str("Lamnidae".to_string())),
+            ("scientificName", DwcValue::Str("Carcharodon carcharias".to_string())),
+        ]);
+
+        assert_eq!(
+            taxon_entity_path(&occurrence, 7),
+            "Animalia/Chordata/Chondrichthyes/Lamniformes/Lamnidae/Carcharodon_carcharias/7"
+        );
+    }
+
+    #[test]
+    fn entity_path_omits_missing_ranks_rather_than_plotting_unknown() {
+        let occurrence = occurrence_with(&[
+            ("kingdom", DwcValue::Str("Animalia".to_string())),
+            ("scientificName", DwcValue::Str("Carcharodon carcharias".to_string())),
+        ]);
+
+        assert_eq!(taxon_entity_path(&occurrence, 0), "Animalia/Carcharodon_carcharias/0");
+    }
+
+    #[test]
+    fn entity_path_falls_back_to_species_then_unclassified() {
+        let occurrence = occurrence_with(&[("species", DwcValue::Str("Carcharodon carcharias".to_string()))]);
+        assert_eq!(taxon_entity_path(&occurrence, 1), "Carcharodon_carcharias/1");
+
+        let occurrence = Occurrence::default();
+        assert_eq!(taxon_entity_path(&occurrence, 2), "Unclassified/2");
+    }
+
+    #[test]
+    fn taxon_color_is_stable_for_the_same_taxon_key() {
+        let occurrence = occurrence_with(&[("taxonKey", DwcValue::Int(2417901))]);
+        assert_eq!(taxon_color(&occurrence), taxon_color(&occurrence));
+    }
+
+    #[test]
+    fn taxon_color_differs_across_distinct_taxon_keys() {
+        let a = occurrence_with(&[("taxonKey", DwcValue::Int(2417901))]);
+        let b = occurrence_with(&[("taxonKey", DwcValue::Int(5220197))]);
+        assert_ne!(taxon_color(&a), taxon_color(&b));
+    }
+
+    #[test]
+    fn taxon_color_always_has_an_opaque_alpha_channel() {
+        let occurrence = occurrence_with(&[("taxonKey", DwcValue::Int(2417901))]);
+        assert_eq!(taxon_color(&occurrence) & 0xFF, 0xFF);
+    }
+
+    #[test]
+    fn hsv_to_rgb_matches_known_primary_colors() {
+        assert_eq!(hsv_to_rgb(0.0, 1.0, 1.0), (255, 0, 0));
+        assert_eq!(hsv_to_rgb(120.0, 1.0, 1.0), (0, 255, 0));
+        assert_eq!(hsv_to_rgb(240.0, 1.0, 1.0), (0, 0, 255));
+    }
+}